@@ -0,0 +1,103 @@
+//! Builds the embedded dictionary snapshot.
+//!
+//! In the spirit of `rust-jmdict`'s JMdict build script, this reads a jbovlaste
+//! XML export from disk (the vendored `data/jbovlaste.xml`, or the file named by
+//! `XAUSTE_VENDORED_XML`; nothing is fetched), drops the `<valsi>` entries whose
+//! word type belongs to a scope that is not enabled via Cargo features, and
+//! re-emits a whitespace-compacted XML document into `OUT_DIR`. The library
+//! then `include_str!`s it so `xauste::dictionary()` needs no setup.
+
+use std::path::PathBuf;
+use std::{env, fs};
+
+fn main() {
+	let source = source_xml();
+	println!("cargo:rerun-if-changed=data/jbovlaste.xml");
+	println!("cargo:rerun-if-env-changed=XAUSTE_VENDORED_XML");
+
+	let keep_experimental = env::var_os("CARGO_FEATURE_SCOPE_EXPERIMENTAL").is_some();
+	let keep_obsolete = env::var_os("CARGO_FEATURE_SCOPE_OBSOLETE").is_some();
+
+	let filtered = filter_and_compact(&source, keep_experimental, keep_obsolete);
+
+	let out = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR not set")).join("dictionary.xml");
+	fs::write(&out, filtered).expect("writing embedded snapshot");
+}
+
+/// Locate the source export: an explicit `XAUSTE_VENDORED_XML` path wins,
+/// otherwise the vendored `data/jbovlaste.xml`.
+fn source_xml() -> String {
+	if let Some(path) = env::var_os("XAUSTE_VENDORED_XML") {
+		return fs::read_to_string(&path).expect("reading XAUSTE_VENDORED_XML");
+	}
+	let vendored = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR"))
+		.join("data")
+		.join("jbovlaste.xml");
+	fs::read_to_string(&vendored).expect("reading vendored data/jbovlaste.xml")
+}
+
+/// Whether a raw `type="..."` attribute value should be kept given the enabled
+/// scopes. Mirrors `WordType::scope` over the on-the-wire spellings.
+fn keep_type(ty: &str, keep_experimental: bool, keep_obsolete: bool) -> bool {
+	match ty {
+		"experimental cmavo" | "experimental gismu" => keep_experimental,
+		"obsolete cmavo" | "obsolete cmevla" | "obsolete fu'ivla" | "obsolete zei-lujvo" => {
+			keep_obsolete
+		}
+		_ => true,
+	}
+}
+
+/// Drop out-of-scope `<valsi>` blocks and collapse inter-element whitespace.
+fn filter_and_compact(source: &str, keep_experimental: bool, keep_obsolete: bool) -> String {
+	let mut out = String::with_capacity(source.len());
+	let mut rest = source;
+
+	while let Some(open) = rest.find("<valsi") {
+		out.push_str(&rest[..open]);
+		let block_end = rest[open..]
+			.find("</valsi>")
+			.map(|end| open + end + "</valsi>".len())
+			.unwrap_or(rest.len());
+		let block = &rest[open..block_end];
+
+		if extract_type(block).is_none_or(|ty| keep_type(ty, keep_experimental, keep_obsolete)) {
+			out.push_str(block);
+		}
+
+		rest = &rest[block_end..];
+	}
+	out.push_str(rest);
+
+	compact_whitespace(&out)
+}
+
+/// Extract the value of the `type="..."` attribute from a `<valsi ...>` block.
+fn extract_type(block: &str) -> Option<&str> {
+	let start = block.find("type=\"")? + "type=\"".len();
+	let len = block[start..].find('"')?;
+	Some(&block[start..start + len])
+}
+
+/// Collapse runs of whitespace that sit entirely between two tags.
+fn compact_whitespace(xml: &str) -> String {
+	let mut out = String::with_capacity(xml.len());
+	let mut chars = xml.char_indices().peekable();
+	while let Some((_, c)) = chars.next() {
+		out.push(c);
+		if c == '>' {
+			// Peek past any whitespace; if the next non-whitespace char opens a
+			// tag, the whitespace was pure indentation and can be dropped.
+			let mut lookahead = chars.clone();
+			let mut saw_whitespace = false;
+			while matches!(lookahead.peek(), Some((_, w)) if w.is_whitespace()) {
+				lookahead.next();
+				saw_whitespace = true;
+			}
+			if saw_whitespace && matches!(lookahead.peek(), Some((_, '<'))) {
+				chars = lookahead;
+			}
+		}
+	}
+	out
+}
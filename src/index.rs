@@ -0,0 +1,207 @@
+//! A lookup index and a small query language over a parsed [`Dictionary`].
+//!
+//! [`Dictionary::index`] builds hash maps that point from each valsi, rafsi,
+//! gloss word, keyword, and natural-language word back to the owning entry, so
+//! callers no longer have to scan the vectors. On top of that, [`Query`] offers
+//! a step/predicate model — in the spirit of `preserves-path` — for asking
+//! things like "gismu whose gloss matches X" or "resolve this rafsi to its
+//! gismu".
+//!
+//! [`Dictionary::index`]: crate::Dictionary::index
+
+use std::collections::HashMap;
+
+use crate::{Dictionary, NlWord, Word, WordType};
+
+/// A read-only index into a [`Dictionary`], borrowing its entries.
+#[derive(Debug, Default)]
+pub struct Index<'d, 'a> {
+	words: Vec<&'d Word<'a>>,
+	by_valsi: HashMap<&'d str, &'d Word<'a>>,
+	by_rafsi: HashMap<&'d str, Vec<&'d Word<'a>>>,
+	by_gloss: HashMap<&'d str, Vec<&'d Word<'a>>>,
+	by_keyword: HashMap<&'d str, Vec<&'d Word<'a>>>,
+	by_nlword: HashMap<&'d str, Vec<&'d NlWord<'a>>>,
+}
+
+impl<'d, 'a> Index<'d, 'a> {
+	pub(crate) fn build(dictionary: &'d Dictionary<'a>) -> Self {
+		let mut index = Index::default();
+
+		for word in &dictionary.lojban_to_english {
+			index.words.push(word);
+			index.by_valsi.insert(&word.word, word);
+			for rafsi in &word.rafsi {
+				index.by_rafsi.entry(rafsi).or_default().push(word);
+			}
+			for gloss in &word.glosses {
+				index.by_gloss.entry(&gloss.word).or_default().push(word);
+			}
+			for keyword in &word.keywords {
+				index.by_keyword.entry(&keyword.word).or_default().push(word);
+			}
+		}
+
+		for nlword in &dictionary.english_to_lojban {
+			index.by_nlword.entry(&nlword.word).or_default().push(nlword);
+		}
+
+		index
+	}
+
+	/// The valsi with the given spelling, if any.
+	#[must_use]
+	pub fn word(&self, valsi: &str) -> Option<&'d Word<'a>> {
+		self.by_valsi.get(valsi).copied()
+	}
+
+	/// Every word carrying the given rafsi.
+	#[must_use]
+	pub fn by_rafsi(&self, rafsi: &str) -> &[&'d Word<'a>] {
+		self.by_rafsi.get(rafsi).map_or(&[], Vec::as_slice)
+	}
+
+	/// Every word with the given gloss word.
+	#[must_use]
+	pub fn by_gloss(&self, gloss: &str) -> &[&'d Word<'a>] {
+		self.by_gloss.get(gloss).map_or(&[], Vec::as_slice)
+	}
+
+	/// Every word with the given keyword.
+	#[must_use]
+	pub fn by_keyword(&self, keyword: &str) -> &[&'d Word<'a>] {
+		self.by_keyword.get(keyword).map_or(&[], Vec::as_slice)
+	}
+
+	/// Every natural-language entry for the given word.
+	#[must_use]
+	pub fn nlwords(&self, word: &str) -> &[&'d NlWord<'a>] {
+		self.by_nlword.get(word).map_or(&[], Vec::as_slice)
+	}
+
+	/// Every word, in dictionary order.
+	#[must_use]
+	pub fn words(&self) -> &[&'d Word<'a>] {
+		&self.words
+	}
+
+	/// Evaluate a [`Query`], returning the words that satisfy every predicate.
+	#[must_use]
+	pub fn query(&self, query: &Query) -> Vec<&'d Word<'a>> {
+		self.words
+			.iter()
+			.copied()
+			.filter(|word| query.predicates.iter().all(|p| p.matches(word)))
+			.collect()
+	}
+}
+
+/// A conjunction of [`Predicate`]s evaluated against an [`Index`].
+#[derive(Debug, Default, Clone)]
+pub struct Query {
+	predicates: Vec<Predicate>,
+}
+
+/// A single filtering step in a [`Query`].
+#[derive(Debug, Clone)]
+pub enum Predicate {
+	/// The valsi spelling equals the given string.
+	Valsi(String),
+	/// The word is of the given type.
+	Type(WordType),
+	/// The word carries the given rafsi.
+	Rafsi(String),
+	/// Some gloss word contains the given substring (ASCII case-insensitive).
+	Gloss(String),
+	/// Some keyword contains the given substring (ASCII case-insensitive).
+	Keyword(String),
+}
+
+impl Predicate {
+	fn matches(&self, word: &Word<'_>) -> bool {
+		match self {
+			Self::Valsi(valsi) => word.word == *valsi,
+			Self::Type(ty) => word.ty == *ty,
+			Self::Rafsi(rafsi) => word.rafsi.iter().any(|r| r == rafsi),
+			Self::Gloss(needle) => word
+				.glosses
+				.iter()
+				.any(|gloss| contains_ignore_ascii_case(&gloss.word, needle)),
+			Self::Keyword(needle) => word
+				.keywords
+				.iter()
+				.any(|keyword| contains_ignore_ascii_case(&keyword.word, needle)),
+		}
+	}
+}
+
+impl Query {
+	/// An empty query, matching every word.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add a predicate, returning the query for chaining.
+	#[must_use]
+	pub fn with(mut self, predicate: Predicate) -> Self {
+		self.predicates.push(predicate);
+		self
+	}
+
+	/// Parse a whitespace-separated list of `field:value` terms.
+	///
+	/// Recognized fields are `valsi`, `type`, `rafsi`, `gloss`, and `keyword`;
+	/// within a `type` value an underscore stands in for a space, so
+	/// `type:experimental_gismu` selects [`WordType::ExperimentalGismu`].
+	///
+	/// # Errors
+	///
+	/// Returns an error for a term with no `:`, an unknown field, or an
+	/// unparseable word type.
+	pub fn parse(raw: &str) -> Result<Self, QueryParseError> {
+		let mut query = Query::new();
+		for term in raw.split_whitespace() {
+			let (field, value) = term
+				.split_once(':')
+				.ok_or_else(|| QueryParseError::MalformedTerm(term.into()))?;
+			let predicate = match field {
+				"valsi" => Predicate::Valsi(value.to_owned()),
+				"type" => Predicate::Type(
+					value
+						.replace('_', " ")
+						.parse()
+						.map_err(|_| QueryParseError::UnknownType(value.into()))?,
+				),
+				"rafsi" => Predicate::Rafsi(value.to_owned()),
+				"gloss" => Predicate::Gloss(value.to_owned()),
+				"keyword" => Predicate::Keyword(value.to_owned()),
+				_ => return Err(QueryParseError::UnknownField(field.into())),
+			};
+			query.predicates.push(predicate);
+		}
+		Ok(query)
+	}
+}
+
+fn contains_ignore_ascii_case(haystack: &str, needle: &str) -> bool {
+	haystack.to_ascii_lowercase().contains(&needle.to_ascii_lowercase())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum QueryParseError {
+	#[error("malformed query term {0:?}, expected field:value")]
+	MalformedTerm(Box<str>),
+	#[error("unknown query field {0:?}")]
+	UnknownField(Box<str>),
+	#[error("unknown word type {0:?}")]
+	UnknownType(Box<str>),
+}
+
+impl Dictionary<'_> {
+	/// Build a lookup [`Index`] over this dictionary's entries.
+	#[must_use]
+	pub fn index(&self) -> Index<'_, '_> {
+		Index::build(self)
+	}
+}
@@ -0,0 +1,130 @@
+//! Merging several per-language [`Dictionary`] exports into one structure whose
+//! words nest their translations by language code.
+//!
+//! jbovlaste publishes the same valsi set under `?lang=<code>`; only the
+//! definition, notes, glosses, and keywords differ between languages, so those
+//! move onto a per-language [`Translation`] while the language-invariant valsi
+//! data stays on the [`MergedWord`].
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{Dictionary, GlossWord, Keyword, NlWord, User, WordType};
+
+/// One language's rendering of a word's meaning.
+#[derive(Debug, Serialize)]
+pub struct Translation<'a> {
+	pub definition: Cow<'a, str>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub notes: Option<Cow<'a, str>>,
+	#[serde(skip_serializing_if = "<[_]>::is_empty")]
+	pub glosses: Vec<GlossWord<'a>>,
+	#[serde(skip_serializing_if = "<[_]>::is_empty")]
+	pub keywords: Vec<Keyword<'a>>,
+	#[cfg(feature = "place-structure")]
+	pub definition_parts: Vec<crate::place::Segment>,
+	#[cfg(feature = "place-structure")]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub notes_parts: Option<Vec<crate::place::Segment>>,
+}
+
+/// A valsi with its meaning keyed by language code.
+#[derive(Debug, Serialize)]
+pub struct MergedWord<'a> {
+	pub word: Cow<'a, str>,
+	#[serde(rename = "type")]
+	pub ty: WordType,
+	pub unofficial: bool,
+	#[serde(skip_serializing_if = "<[_]>::is_empty")]
+	pub rafsi: Vec<Cow<'a, str>>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub selmaho: Option<Cow<'a, str>>,
+	pub user: User<'a>,
+	pub definition_id: u32,
+	pub translations: BTreeMap<String, Translation<'a>>,
+	#[cfg(feature = "place-structure")]
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub places: Vec<u8>,
+}
+
+/// Several language exports merged by valsi/`definition_id`.
+///
+/// The lojban-to-English direction collapses into [`MergedWord`]s; the
+/// natural-language direction stays split per language, since those entries are
+/// inherently language-specific.
+#[derive(Debug, Serialize)]
+pub struct MergedDictionary<'a> {
+	pub words: Vec<MergedWord<'a>>,
+	pub nlwords: BTreeMap<String, Vec<NlWord<'a>>>,
+}
+
+impl<'a> MergedDictionary<'a> {
+	/// Merge `(language code, dictionary)` pairs into a single structure.
+	///
+	/// Words are matched on their valsi and `definition_id`; the first language
+	/// to introduce a word contributes its language-invariant fields (type,
+	/// rafsi, selmaho, submitting user), and every language contributes a
+	/// [`Translation`] under its code.
+	pub fn merge<I>(langs: I) -> Self
+	where
+		I: IntoIterator<Item = (String, Dictionary<'a>)>,
+	{
+		let mut words: Vec<MergedWord<'a>> = Vec::new();
+		let mut index: HashMap<(Cow<'a, str>, u32), usize> = HashMap::new();
+		let mut nlwords = BTreeMap::new();
+
+		for (lang, dict) in langs {
+			for word in dict.lojban_to_english {
+				#[cfg(feature = "place-structure")]
+				let definition_parts = word.definition_parts().parts;
+				#[cfg(feature = "place-structure")]
+				let notes_parts = word.notes_parts().map(|parsed| parsed.parts);
+				#[cfg(feature = "place-structure")]
+				let word_places = word.places();
+
+				let translation = Translation {
+					definition: word.definition,
+					notes: word.notes,
+					glosses: word.glosses,
+					keywords: word.keywords,
+					#[cfg(feature = "place-structure")]
+					definition_parts,
+					#[cfg(feature = "place-structure")]
+					notes_parts,
+				};
+				let key = (word.word.clone(), word.definition_id);
+				if let Some(&existing) = index.get(&key) {
+					words[existing].translations.insert(lang.clone(), translation);
+					#[cfg(feature = "place-structure")]
+					for place in word_places {
+						if !words[existing].places.contains(&place) {
+							words[existing].places.push(place);
+						}
+					}
+				} else {
+					let mut translations = BTreeMap::new();
+					translations.insert(lang.clone(), translation);
+					index.insert(key, words.len());
+					words.push(MergedWord {
+						word: word.word,
+						ty: word.ty,
+						unofficial: word.unofficial,
+						rafsi: word.rafsi,
+						selmaho: word.selmaho,
+						user: word.user,
+						definition_id: word.definition_id,
+						translations,
+						#[cfg(feature = "place-structure")]
+						places: word_places,
+					});
+				}
+			}
+			nlwords.insert(lang, dict.english_to_lojban);
+		}
+
+		Self { words, nlwords }
+	}
+}
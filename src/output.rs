@@ -0,0 +1,67 @@
+//! Output-format selection for serializing a dictionary.
+//!
+//! Every data type derives [`serde::Serialize`], so alongside the textual JSON
+//! form we can offer the compact binary CBOR and `MessagePack` encodings for
+//! consumers that embed or cache the result.
+
+use std::io::Write;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+/// A serialization target chosen on the command line via `--format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+	#[default]
+	Json,
+	Cbor,
+	MessagePack,
+}
+
+impl OutputFormat {
+	/// Serialize `value` into `writer` using this format.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the chosen encoder fails, e.g. on an I/O failure
+	/// while writing.
+	pub fn write<W: Write, T: Serialize>(
+		self,
+		mut writer: W,
+		value: &T,
+	) -> Result<(), OutputError> {
+		match self {
+			Self::Json => serde_json::to_writer(writer, value)?,
+			Self::Cbor => ciborium::into_writer(value, writer)?,
+			Self::MessagePack => rmp_serde::encode::write(&mut writer, value)?,
+		}
+		Ok(())
+	}
+}
+
+impl FromStr for OutputFormat {
+	type Err = OutputFormatFromStrError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s {
+			"json" => Self::Json,
+			"cbor" => Self::Cbor,
+			"msgpack" | "messagepack" => Self::MessagePack,
+			_ => return Err(OutputFormatFromStrError(s.into())),
+		})
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid output format {0:?}")]
+pub struct OutputFormatFromStrError(Box<str>);
+
+#[derive(Debug, thiserror::Error)]
+pub enum OutputError {
+	#[error("json: {0}")]
+	Json(#[from] serde_json::Error),
+	#[error("cbor: {0}")]
+	Cbor(#[from] ciborium::ser::Error<std::io::Error>),
+	#[error("msgpack: {0}")]
+	MessagePack(#[from] rmp_serde::encode::Error),
+}
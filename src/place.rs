@@ -0,0 +1,274 @@
+//! Parsing of the TeX-style place-structure markup and HTML entities that
+//! jbovlaste embeds in `definition` and `notes` fields.
+//!
+//! A definition like `$x_1$ talks to $x_2$ &amp; listens` carries place
+//! variables written `$x_N$` or `$x_{N}$`, bracketed subscripts, and HTML
+//! entities. [`parse`] walks the string a character at a time — in the spirit
+//! of html5lib's `unescape` routine — and emits a typed [`Segment`] sequence
+//! plus the distinct place numbers it referenced.
+
+use serde::Serialize;
+
+/// One piece of a parsed definition or notes string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Segment {
+	/// A run of literal text.
+	Text(String),
+	/// A place variable, normalized to its integer subscript (`$x_3$` → `3`).
+	Place(u8),
+	/// A decoded HTML entity, e.g. `&amp;` → `&`.
+	Entity(String),
+}
+
+/// The result of parsing a field: its segments and the distinct place numbers
+/// it referenced, in order of first appearance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Parsed {
+	pub parts: Vec<Segment>,
+	pub places: Vec<u8>,
+}
+
+/// Parse place markup and entities out of `input`.
+///
+/// Unmatched `$` and unknown entities are preserved as literal text; braces in
+/// a `$x_{N}$` subscript are consumed as a balanced group before text resumes.
+#[must_use]
+pub fn parse(input: &str) -> Parsed {
+	let chars: Vec<char> = input.chars().collect();
+	let mut parts = Vec::new();
+	let mut places = Vec::new();
+	let mut text = String::new();
+	let mut i = 0;
+
+	while i < chars.len() {
+		match chars[i] {
+			'$' => {
+				if let Some((place, next)) = read_place(&chars, i + 1) {
+					if let Some(place) = place {
+						flush(&mut parts, &mut text);
+						parts.push(Segment::Place(place));
+						if !places.contains(&place) {
+							places.push(place);
+						}
+					} else {
+						// A marker whose subscript is not a number stays literal
+						// text, consumed whole up to its closing `$`.
+						text.extend(&chars[i..next]);
+					}
+					i = next;
+					continue;
+				}
+				// A lone `$` that does not open a place marker is literal.
+				text.push('$');
+				i += 1;
+			}
+			'&' => {
+				if let Some((decoded, next)) = read_entity(&chars, i + 1) {
+					flush(&mut parts, &mut text);
+					parts.push(Segment::Entity(decoded));
+					i = next;
+					continue;
+				}
+				// Unknown entity: pass the `&` through and keep scanning the
+				// rest verbatim.
+				text.push('&');
+				i += 1;
+			}
+			other => {
+				text.push(other);
+				i += 1;
+			}
+		}
+	}
+
+	flush(&mut parts, &mut text);
+	Parsed { parts, places }
+}
+
+fn flush(parts: &mut Vec<Segment>, text: &mut String) {
+	if !text.is_empty() {
+		parts.push(Segment::Text(std::mem::take(text)));
+	}
+}
+
+/// Try to read a `x_N` / `x_{N}` place marker terminated by `$`, starting just
+/// past the opening `$`. Returns the place number (`None` when the subscript is
+/// not a number) and the index past the closing `$`.
+fn read_place(chars: &[char], start: usize) -> Option<(Option<u8>, usize)> {
+	let mut i = start;
+	if chars.get(i) != Some(&'x') || chars.get(i + 1) != Some(&'_') {
+		return None;
+	}
+	i += 2;
+
+	let digits = if chars.get(i) == Some(&'{') {
+		// Consume a balanced brace group, collecting its contents.
+		let mut depth = 0;
+		let mut inner = String::new();
+		while i < chars.len() {
+			match chars[i] {
+				'{' => {
+					depth += 1;
+					if depth > 1 {
+						inner.push('{');
+					}
+				}
+				'}' => {
+					depth -= 1;
+					if depth == 0 {
+						i += 1;
+						break;
+					}
+					inner.push('}');
+				}
+				c => inner.push(c),
+			}
+			i += 1;
+		}
+		if depth != 0 {
+			return None;
+		}
+		inner
+	} else {
+		let mut inner = String::new();
+		while let Some(c) = chars.get(i) {
+			if c.is_ascii_digit() {
+				inner.push(*c);
+				i += 1;
+			} else {
+				break;
+			}
+		}
+		inner
+	};
+
+	if chars.get(i) != Some(&'$') {
+		return None;
+	}
+	Some((digits.parse().ok(), i + 1))
+}
+
+/// Try to decode an HTML entity starting just past the opening `&`. Returns the
+/// decoded text and the index past the closing `;`.
+fn read_entity(chars: &[char], start: usize) -> Option<(String, usize)> {
+	let mut i = start;
+	let mut name = String::new();
+	while let Some(&c) = chars.get(i) {
+		if c == ';' {
+			break;
+		}
+		if c.is_ascii_alphanumeric() || c == '#' {
+			name.push(c);
+			i += 1;
+		} else {
+			return None;
+		}
+	}
+	if chars.get(i) != Some(&';') || name.is_empty() {
+		return None;
+	}
+
+	let decoded = decode_entity(&name)?;
+	Some((decoded, i + 1))
+}
+
+fn decode_entity(name: &str) -> Option<String> {
+	if let Some(number) = name.strip_prefix('#') {
+		let code = if let Some(hex) = number.strip_prefix(['x', 'X']) {
+			u32::from_str_radix(hex, 16).ok()?
+		} else {
+			number.parse().ok()?
+		};
+		return char::from_u32(code).map(String::from);
+	}
+	let ch = match name {
+		"amp" => '&',
+		"lt" => '<',
+		"gt" => '>',
+		"quot" => '"',
+		"apos" => '\'',
+		"nbsp" => '\u{a0}',
+		_ => return None,
+	};
+	Some(ch.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn text(s: &str) -> Segment {
+		Segment::Text(s.to_owned())
+	}
+
+	#[test]
+	fn places_and_text() {
+		let parsed = parse("$x_1$ talks to $x_2$");
+		assert_eq!(
+			parsed.parts,
+			vec![
+				Segment::Place(1),
+				text(" talks to "),
+				Segment::Place(2),
+			]
+		);
+		assert_eq!(parsed.places, vec![1, 2]);
+	}
+
+	#[test]
+	fn braced_subscript() {
+		let parsed = parse("fills $x_{3}$ here");
+		assert_eq!(
+			parsed.parts,
+			vec![text("fills "), Segment::Place(3), text(" here")]
+		);
+		assert_eq!(parsed.places, vec![3]);
+	}
+
+	#[test]
+	fn distinct_places_in_order() {
+		let parsed = parse("$x_2$ $x_1$ $x_2$");
+		assert_eq!(parsed.places, vec![2, 1]);
+	}
+
+	#[test]
+	fn lone_dollar_is_literal() {
+		let parsed = parse("costs $5 and $x_1$");
+		assert_eq!(
+			parsed.parts,
+			vec![text("costs $5 and "), Segment::Place(1)]
+		);
+		assert_eq!(parsed.places, vec![1]);
+	}
+
+	#[test]
+	fn nested_braces_consumed() {
+		// A non-numeric subscript is not a place; its whole brace group is
+		// consumed, so the trailing `$` does not reopen a marker.
+		let parsed = parse("$x_{a{b}c}$x_1$");
+		assert_eq!(parsed.places, Vec::<u8>::new());
+	}
+
+	#[test]
+	fn named_and_numeric_entities() {
+		let parsed = parse("a &amp; b &#65; c &#x42;");
+		assert_eq!(
+			parsed.parts,
+			vec![
+				text("a "),
+				Segment::Entity("&".to_owned()),
+				text(" b "),
+				Segment::Entity("A".to_owned()),
+				text(" c "),
+				Segment::Entity("B".to_owned()),
+			]
+		);
+	}
+
+	#[test]
+	fn unknown_entity_is_verbatim() {
+		let parsed = parse("keep &frobnicate; intact");
+		assert_eq!(parsed.parts, vec![text("keep &frobnicate; intact")]);
+	}
+}